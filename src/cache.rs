@@ -0,0 +1,106 @@
+//! Local cache for the downloaded/unpacked NOAA alerts shapefile.
+//!
+//! Every lookup used to redownload and untar `current_all.tar.gz`, which is
+//! wasteful for repeated or `--watch` runs. This stores the unpacked files
+//! under the OS cache dir and only re-fetches once they're older than a TTL,
+//! using `If-Modified-Since` so a `304 Not Modified` just refreshes the
+//! cache's timestamp instead of re-downloading the archive.
+
+use crate::WxError;
+use flate2::read::GzDecoder;
+use reqwest::header::IF_MODIFIED_SINCE;
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tar::Archive;
+use tempfile::NamedTempFile;
+
+const ALERTS_URL: &str =
+  "https://tgftp.nws.noaa.gov/SL.us008001/DF.sha/DC.cap/DS.WWA/current_all.tar.gz";
+
+/// Default cache TTL, matching the NWS update cadence.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+fn cache_dir() -> PathBuf {
+  dirs::cache_dir()
+    .unwrap_or_else(std::env::temp_dir)
+    .join("wxwarn")
+}
+
+fn shapefile_path() -> PathBuf {
+  cache_dir().join("current_all.shp")
+}
+
+fn timestamp_path() -> PathBuf {
+  cache_dir().join(".last-fetched")
+}
+
+fn fetched_at() -> Option<SystemTime> {
+  fs::metadata(timestamp_path()).and_then(|m| m.modified()).ok()
+}
+
+/// True if the cached shapefile is missing or older than `ttl`.
+pub fn is_stale(ttl: Duration) -> bool {
+  if !shapefile_path().exists() {
+    return true;
+  }
+  match fetched_at() {
+    Some(t) => SystemTime::now().duration_since(t).map_or(true, |age| age > ttl),
+    None => true,
+  }
+}
+
+fn http_date(t: SystemTime) -> String {
+  let dt: chrono::DateTime<chrono::Utc> = t.into();
+  dt.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+fn touch_timestamp(dir: &std::path::Path) -> Result<(), WxError> {
+  File::create(dir.join(".last-fetched")).map_err(WxError::Io)?;
+  Ok(())
+}
+
+/// Ensure the cached, unpacked shapefile is fresh (within `ttl`, unless
+/// `force` is set), downloading and unpacking a new copy if needed, and
+/// return the path to `current_all.shp`.
+pub fn ensure_fresh(
+  client: &reqwest::blocking::Client,
+  ttl: Duration,
+  force: bool,
+) -> Result<PathBuf, WxError> {
+
+  let dir = cache_dir();
+  fs::create_dir_all(&dir).map_err(WxError::Io)?;
+
+  if !force && !is_stale(ttl) {
+    return Ok(shapefile_path());
+  }
+
+  let mut request = client.get(ALERTS_URL);
+
+  if !force {
+    if let Some(t) = fetched_at() {
+      request = request.header(IF_MODIFIED_SINCE, http_date(t));
+    }
+  }
+
+  let mut resp = request.send().map_err(WxError::Download)?;
+
+  if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+    touch_timestamp(&dir)?;
+    return Ok(shapefile_path());
+  }
+
+  let mut tf = NamedTempFile::new().map_err(WxError::Io)?;
+  io::copy(&mut resp, &mut tf).map_err(WxError::Io)?;
+
+  let current_all_tar_gz = File::open(tf.path()).map_err(WxError::Io)?;
+  let current_all_tar = GzDecoder::new(current_all_tar_gz);
+  let mut archive = Archive::new(current_all_tar);
+  archive.unpack(&dir).map_err(WxError::Untar)?;
+
+  touch_timestamp(&dir)?;
+
+  Ok(shapefile_path())
+}
@@ -0,0 +1,66 @@
+//! Error type for the library surface of this crate.
+//!
+//! Every fallible step in fetching, unpacking, and reading the NOAA alerts
+//! shapefile (plus the per-polygon CAP lookup) is collected into a single
+//! `WxError` so callers get a `Result` instead of a panic.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum WxError {
+    /// Downloading `current_all.tar.gz` failed.
+    Download(reqwest::Error),
+    /// Creating the cache directory or its timestamp file, or writing the
+    /// downloaded archive to a temp file and reading it back, failed.
+    Io(std::io::Error),
+    /// Decompressing/untarring the downloaded archive failed.
+    Untar(std::io::Error),
+    /// Opening or reading the `current_all.shp` shapefile failed.
+    Shapefile(shapefile::Error),
+    /// A DBF field we rely on (e.g. `CAP_ID`) was not present in the record.
+    MissingField(&'static str),
+    /// A DBF field we rely on was present but not the type we expected.
+    FieldType(&'static str),
+    /// The per-alert request to api.weather.gov failed.
+    Http(reqwest::Error),
+    /// The per-alert response body was not valid `Alert` JSON.
+    Json(serde_json::Error),
+    /// The `--place` forward-geocoding request itself failed.
+    Geocode(geocoding::GeocodingError),
+    /// The `--place` geocoder ran fine but returned no matches.
+    NoGeocodeMatch(String),
+}
+
+impl fmt::Display for WxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WxError::Download(e) => write!(f, "failed to download alerts shapefile: {}", e),
+            WxError::Io(e) => write!(f, "failed to read/write cached or temporary alerts data: {}", e),
+            WxError::Untar(e) => write!(f, "failed to unpack alerts archive: {}", e),
+            WxError::Shapefile(e) => write!(f, "failed to read alerts shapefile: {}", e),
+            WxError::MissingField(name) => write!(f, "field '{}' is not within the record", name),
+            WxError::FieldType(name) => write!(f, "expected '{}' to be a character field", name),
+            WxError::Http(e) => write!(f, "failed to fetch alert from api.weather.gov: {}", e),
+            WxError::Json(e) => write!(f, "failed to decode alert JSON: {}", e),
+            WxError::Geocode(e) => write!(f, "failed to geocode place name: {}", e),
+            WxError::NoGeocodeMatch(place) => write!(f, "no geocoding match found for '{}'", place),
+        }
+    }
+}
+
+impl std::error::Error for WxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WxError::Download(e) => Some(e),
+            WxError::Io(e) => Some(e),
+            WxError::Untar(e) => Some(e),
+            WxError::Shapefile(e) => Some(e),
+            WxError::MissingField(_) => None,
+            WxError::FieldType(_) => None,
+            WxError::Http(e) => Some(e),
+            WxError::Json(e) => Some(e),
+            WxError::Geocode(e) => Some(e),
+            WxError::NoGeocodeMatch(_) => None,
+        }
+    }
+}
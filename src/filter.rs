@@ -0,0 +1,245 @@
+//! Post-processing on top of the raw per-polygon CAP matches: collapse
+//! overlapping/superseded alerts down to the latest per zone, and let
+//! callers further narrow by severity or lead time.
+
+use crate::Alert;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+
+/// A polygon match paired with the DBF fields needed to dedup it
+/// (`PROD_TYPE`/`ISSUANCE`), alongside the CAP `Alert` fetched for it.
+pub struct MatchedAlert {
+    pub prod_type: String,
+    pub issuance: String,
+    pub alert: Alert,
+}
+
+/// Keep only the newest `MatchedAlert` per `(PROD_TYPE, VTEC)` group, then
+/// drop any surviving alert whose `id` is listed as a reference or expired
+/// reference of another surviving alert (it's been superseded by a CAP
+/// update chain).
+pub fn dedup_latest(matches: Vec<MatchedAlert>) -> Vec<Alert> {
+
+  let mut groups: HashMap<(String, String), Vec<MatchedAlert>> = HashMap::new();
+
+  for m in matches {
+    let vtec_key = m.alert.properties.parameters.vtec.join(",");
+    groups.entry((m.prod_type.clone(), vtec_key)).or_default().push(m);
+  }
+
+  let mut kept: Vec<Alert> = Vec::new();
+
+  for mut group in groups.into_values() {
+    group.sort_by_key(issued_at);
+    if let Some(latest) = group.pop() {
+      kept.push(latest.alert);
+    }
+  }
+
+  let superseded: HashSet<String> = kept
+    .iter()
+    .flat_map(|a| {
+      let mut ids: Vec<String> = a.properties.references.iter().map(|r| r.id.clone()).collect();
+      if let Some(expired) = &a.properties.parameters.expired_references {
+        ids.extend(expired.iter().cloned());
+      }
+      ids
+    })
+    .collect();
+
+  kept.into_iter().filter(|a| !superseded.contains(&a.id)).collect()
+}
+
+/// The timestamp used to order a `MatchedAlert` within its dedup group:
+/// the DBF `ISSUANCE` field (format `YYYYMMDDHHMM`) if it parses, otherwise
+/// the CAP `sent` timestamp, otherwise the Unix epoch so malformed data
+/// sorts first rather than panicking or erroring out the whole lookup.
+fn issued_at(m: &MatchedAlert) -> DateTime<Utc> {
+  parse_issuance(&m.issuance)
+    .or_else(|| m.alert.properties.sent.parse::<DateTime<Utc>>().ok())
+    .unwrap_or_else(|| Utc.timestamp_opt(0, 0).unwrap())
+}
+
+fn parse_issuance(issuance: &str) -> Option<DateTime<Utc>> {
+  chrono::NaiveDateTime::parse_from_str(issuance, "%Y%m%d%H%M")
+    .ok()
+    .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+/// NWS CAP severity levels, ordered from least to most severe.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Minor,
+    Moderate,
+    Severe,
+    Extreme,
+}
+
+impl Severity {
+    fn from_cap(severity: &str) -> Option<Severity> {
+        match severity {
+            "Minor" => Some(Severity::Minor),
+            "Moderate" => Some(Severity::Moderate),
+            "Severe" => Some(Severity::Severe),
+            "Extreme" => Some(Severity::Extreme),
+            _ => None,
+        }
+    }
+}
+
+/// Drop alerts below `min`. An alert whose CAP severity isn't one of the
+/// known levels (e.g. "Unknown") is treated as below every threshold.
+pub fn filter_min_severity(alerts: Vec<Alert>, min: Severity) -> Vec<Alert> {
+    alerts
+        .into_iter()
+        .filter(|a| Severity::from_cap(&a.properties.severity).is_some_and(|s| s >= min))
+        .collect()
+}
+
+/// Drop alerts whose `onset` is more than `hours` out from now, or whose
+/// `onset` can't be parsed.
+pub fn filter_within_hours(alerts: Vec<Alert>, hours: i64) -> Vec<Alert> {
+    let now = Utc::now();
+    let horizon = Duration::hours(hours);
+
+    alerts
+        .into_iter()
+        .filter(|a| {
+            a.properties
+                .onset
+                .parse::<DateTime<Utc>>()
+                .map(|onset| onset - now <= horizon)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Geocode, Parameters, Properties};
+
+  fn alert(id: &str, vtec: &str, severity: &str, onset: &str, sent: &str) -> Alert {
+    Alert {
+      context: vec![],
+      id: id.to_string(),
+      alert_type: "Feature".to_string(),
+      geometry: serde_json::Value::Null,
+      properties: Properties {
+        id: id.to_string(),
+        properties_type: "Feature".to_string(),
+        properties_id: id.to_string(),
+        area_desc: "Test Zone".to_string(),
+        geocode: Geocode { same: vec![], ugc: vec![] },
+        affected_zones: vec![],
+        references: vec![],
+        sent: sent.to_string(),
+        effective: sent.to_string(),
+        onset: onset.to_string(),
+        expires: sent.to_string(),
+        ends: sent.to_string(),
+        status: "Actual".to_string(),
+        message_type: "Alert".to_string(),
+        category: "Met".to_string(),
+        severity: severity.to_string(),
+        certainty: "Observed".to_string(),
+        urgency: "Immediate".to_string(),
+        event: "Test Event".to_string(),
+        sender: "w-nws.webmaster@noaa.gov".to_string(),
+        sender_name: "NWS".to_string(),
+        headline: "Test Headline".to_string(),
+        description: "Test Description".to_string(),
+        instruction: "Test Instruction".to_string(),
+        response: "Monitor".to_string(),
+        parameters: Parameters {
+          awip_sidentifier: vec![],
+          wm_oidentifier: vec![],
+          nw_sheadline: vec![],
+          blockchannel: vec![],
+          vtec: vec![vtec.to_string()],
+          event_ending_time: vec![],
+          expired_references: None,
+        },
+      },
+    }
+  }
+
+  fn matched(alert: Alert, prod_type: &str, issuance: &str) -> MatchedAlert {
+    MatchedAlert { prod_type: prod_type.to_string(), issuance: issuance.to_string(), alert }
+  }
+
+  #[test]
+  fn dedup_keeps_newest_issuance_in_a_group() {
+    let older = matched(
+      alert("https://api.weather.gov/alerts/1", "VTEC.1", "Severe", "2024-01-01T00:00:00+00:00", "2024-01-01T00:00:00+00:00"),
+      "WARN",
+      "202401010000",
+    );
+    let newer = matched(
+      alert("https://api.weather.gov/alerts/2", "VTEC.1", "Severe", "2024-01-02T00:00:00+00:00", "2024-01-02T00:00:00+00:00"),
+      "WARN",
+      "202401020000",
+    );
+
+    let kept = dedup_latest(vec![older, newer]);
+
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].id, "https://api.weather.gov/alerts/2");
+  }
+
+  #[test]
+  fn dedup_drops_alerts_superseded_via_references() {
+    let mut superseding = alert(
+      "https://api.weather.gov/alerts/new",
+      "VTEC.new",
+      "Severe",
+      "2024-01-02T00:00:00+00:00",
+      "2024-01-02T00:00:00+00:00",
+    );
+    superseding.properties.references.push(crate::Reference {
+      id: "https://api.weather.gov/alerts/old".to_string(),
+      identifier: "old".to_string(),
+      sender: "w-nws.webmaster@noaa.gov".to_string(),
+      sent: "2024-01-01T00:00:00+00:00".to_string(),
+    });
+
+    let superseded = alert(
+      "https://api.weather.gov/alerts/old",
+      "VTEC.old",
+      "Severe",
+      "2024-01-01T00:00:00+00:00",
+      "2024-01-01T00:00:00+00:00",
+    );
+
+    let kept = dedup_latest(vec![
+      matched(superseding, "WARN", "202401020000"),
+      matched(superseded, "WARN", "202401010000"),
+    ]);
+
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].id, "https://api.weather.gov/alerts/new");
+  }
+
+  #[test]
+  fn min_severity_drops_below_threshold_and_unknown() {
+    let alerts = vec![
+      alert("1", "V1", "Minor", "2024-01-01T00:00:00+00:00", "2024-01-01T00:00:00+00:00"),
+      alert("2", "V2", "Extreme", "2024-01-01T00:00:00+00:00", "2024-01-01T00:00:00+00:00"),
+      alert("3", "V3", "Unknown", "2024-01-01T00:00:00+00:00", "2024-01-01T00:00:00+00:00"),
+    ];
+
+    let kept = filter_min_severity(alerts, Severity::Moderate);
+
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].id, "2");
+  }
+
+  #[test]
+  fn within_hours_drops_unparseable_onset() {
+    let alerts = vec![alert("1", "V1", "Severe", "not-a-timestamp", "2024-01-01T00:00:00+00:00")];
+
+    let kept = filter_within_hours(alerts, 24);
+
+    assert!(kept.is_empty());
+  }
+}
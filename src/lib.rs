@@ -0,0 +1,301 @@
+//! Display NOAA Weather Alerts For A Given Lat/Lon
+//!
+//! Grabs the NOAA weather alerts shapefile, checks to see if
+//! there are any alerts for the given coordinate, and prints
+//! them if there are.
+//!
+//! # Examples
+//!
+//! ## Rust
+//!
+//! ```no_run
+//! extern crate wxwarn;
+//! wxwarn::print_alert(43.2683199, -70.8635506);
+//! ```
+//!
+//! ## Command line
+//!
+//! ```text
+//! $ wxwarn --lat="43.2683199" --lon="-70.8635506"
+//! ```
+//!
+//! ## Building
+//!
+//! ```text
+//! git clone git@github.com:hrbrmstr/wxwarn
+//! cargo build --release
+//! ```
+//!
+//! ## Installing
+//!
+//! The following will put:
+//!
+//! - `wxwarn`
+//!
+//! into `~/.cargo/bin` unless you've modified the behaviour of `cargo install`.
+//!
+//! ```text
+//! $ cargo install --git https://github.com/hrbrmstr/wxwarn
+//! ```
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+pub mod cache;
+mod error;
+pub mod filter;
+pub mod report;
+pub mod watch;
+
+pub use error::WxError;
+
+use geo::prelude::Contains;
+use std::time::Duration;
+
+/* -------------------------------------------------------------------------- */
+/*            Helpers for parsing NOAA Weather API Alert responses            */
+/* -------------------------------------------------------------------------- */
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Alert {
+    #[serde(rename = "@context")]
+    pub context: Vec<ContextElement>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub alert_type: String,
+    pub geometry: serde_json::Value,
+    pub properties: Properties,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContextClass {
+    #[serde(rename = "@version")]
+    pub version: String,
+    pub wx: String,
+    #[serde(rename = "@vocab")]
+    pub vocab: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Properties {
+    #[serde(rename = "@id")]
+    pub id: String,
+    #[serde(rename = "@type")]
+    pub properties_type: String,
+    #[serde(rename = "id")]
+    pub properties_id: String,
+    #[serde(rename = "areaDesc")]
+    pub area_desc: String,
+    pub geocode: Geocode,
+    #[serde(rename = "affectedZones")]
+    pub affected_zones: Vec<String>,
+    pub references: Vec<Reference>,
+    pub sent: String,
+    pub effective: String,
+    pub onset: String,
+    pub expires: String,
+    pub ends: String,
+    pub status: String,
+    #[serde(rename = "messageType")]
+    pub message_type: String,
+    pub category: String,
+    pub severity: String,
+    pub certainty: String,
+    pub urgency: String,
+    pub event: String,
+    pub sender: String,
+    #[serde(rename = "senderName")]
+    pub sender_name: String,
+    pub headline: String,
+    pub description: String,
+    pub instruction: String,
+    pub response: String,
+    pub parameters: Parameters,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Geocode {
+    #[serde(rename = "SAME")]
+    pub same: Vec<String>,
+    #[serde(rename = "UGC")]
+    pub ugc: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Parameters {
+    #[serde(rename = "AWIPSidentifier")]
+    pub awip_sidentifier: Vec<String>,
+    #[serde(rename = "WMOidentifier")]
+    pub wm_oidentifier: Vec<String>,
+    #[serde(rename = "NWSheadline")]
+    pub nw_sheadline: Vec<String>,
+    #[serde(rename = "BLOCKCHANNEL")]
+    pub blockchannel: Vec<String>,
+    #[serde(rename = "VTEC")]
+    pub vtec: Vec<String>,
+    #[serde(rename = "eventEndingTime")]
+    pub event_ending_time: Vec<String>,
+    #[serde(rename = "expiredReferences")]
+    pub expired_references: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Reference {
+    #[serde(rename = "@id")]
+    pub id: String,
+    pub identifier: String,
+    pub sender: String,
+    pub sent: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ContextElement {
+    ContextClass(ContextClass),
+    String(String),
+}
+
+/// Load the NOAA alerts shapefile (downloading it only when the cached copy
+/// is older than [`cache::DEFAULT_TTL`]), find every polygon containing
+/// `(lat, lon)`, fetch the full CAP `Alert` for each match from
+/// api.weather.gov, and return only the latest alert per `PROD_TYPE`/VTEC
+/// group (see [`filter::dedup_latest`]).
+///
+/// This is the non-panicking library entry point: downstream programs
+/// (status bars, bots, etc.) can call it directly and handle `WxError`
+/// however suits them, instead of relying on `print_alert`'s stdout output.
+pub fn alerts_for(lat: f64, lon: f64) -> Result<Vec<Alert>, WxError> {
+  let builder = reqwest::blocking::ClientBuilder::new();
+  let client = builder.build().map_err(WxError::Download)?;
+
+  alerts_for_with_client(&client, lat, lon)
+}
+
+/// Same as [`alerts_for`], but reuses a caller-supplied client instead of
+/// building a fresh one. Callers that poll repeatedly (e.g. watch mode)
+/// should build one `Client` up front and pass it in on every poll. Uses the
+/// cached shapefile ([`cache::DEFAULT_TTL`]) rather than always re-fetching.
+pub fn alerts_for_with_client(
+  client: &reqwest::blocking::Client,
+  lat: f64,
+  lon: f64,
+) -> Result<Vec<Alert>, WxError> {
+  alerts_for_cached(client, lat, lon, cache::DEFAULT_TTL, false)
+}
+
+/// Same as [`alerts_for_with_client`], but with explicit control over the
+/// cache TTL and whether to force a re-download (`--refresh`).
+pub fn alerts_for_cached(
+  client: &reqwest::blocking::Client,
+  lat: f64,
+  lon: f64,
+  ttl: Duration,
+  force_refresh: bool,
+) -> Result<Vec<Alert>, WxError> {
+
+  let shapefile_path = cache::ensure_fresh(client, ttl, force_refresh)?;
+
+  let polygons = shapefile::read_as::<_, shapefile::Polygon, shapefile::dbase::Record>(
+    shapefile_path,
+  )
+  .map_err(WxError::Shapefile)?;
+
+  let mut matches = Vec::new();
+
+  // go through each polygon. if our location is within one of the polygons
+  // get the relevant info to use with the NOAA API
+  for (polygon, polygon_record) in polygons {
+
+    let geo_polygon: geo::MultiPolygon<f64> = polygon.into();
+
+    if geo_polygon.contains(&geo::point!(x: lon, y: lat)) {
+
+      let cap_id = match polygon_record.get("CAP_ID") {
+        Some(shapefile::dbase::FieldValue::Character(Some(x))) => x,
+        Some(_) => return Err(WxError::FieldType("CAP_ID")),
+        None => return Err(WxError::MissingField("CAP_ID")),
+      };
+
+      let prod_type = match polygon_record.get("PROD_TYPE") {
+        Some(shapefile::dbase::FieldValue::Character(Some(x))) => x,
+        Some(_) => return Err(WxError::FieldType("PROD_TYPE")),
+        None => return Err(WxError::MissingField("PROD_TYPE")),
+      };
+
+      let issuance = match polygon_record.get("ISSUANCE") {
+        Some(shapefile::dbase::FieldValue::Character(Some(x))) => x,
+        Some(_) => return Err(WxError::FieldType("ISSUANCE")),
+        None => return Err(WxError::MissingField("ISSUANCE")),
+      };
+
+      let resp = client
+        .get(format!("https://api.weather.gov/alerts/{}", cap_id))
+        .header("User-Agent", "(rud.is, bob@rud.is)")
+        .header("Accept", "application/geo+json")
+        .send()
+        .map_err(WxError::Http)?;
+
+      let body = resp.text().map_err(WxError::Http)?;
+      let alert: Alert = serde_json::from_str(&body).map_err(WxError::Json)?;
+
+      matches.push(filter::MatchedAlert {
+        prod_type: prod_type.clone(),
+        issuance: issuance.clone(),
+        alert,
+      });
+    }
+
+  }
+
+  // mimic the R script and only show the latest alert per zone: dedup by
+  // PROD_TYPE/VTEC, keeping the most recent ISSUANCE, then drop anything
+  // superseded by a newer CAP update
+  Ok(filter::dedup_latest(matches))
+}
+
+/// Resolve a free-form place name (e.g. "Portsmouth, NH") to a `(lat, lon)`
+/// pair using the OpenStreetMap/Nominatim forward geocoder, returning the
+/// first match.
+pub fn geocode_place(place: &str) -> Result<(f64, f64), WxError> {
+  use geocoding::{Forward, Openstreetmap, Point};
+
+  let osm = Openstreetmap::new();
+
+  let points: Vec<Point<f64>> = osm.forward(place).map_err(WxError::Geocode)?;
+
+  let point = points
+    .into_iter()
+    .next()
+    .ok_or_else(|| WxError::NoGeocodeMatch(place.to_string()))?;
+
+  Ok((point.y(), point.x()))
+}
+
+/// Print `alerts` to stdout in the original human-readable format.
+pub fn print_alerts(alerts: &[Alert]) {
+
+  for (i, alert) in alerts.iter().enumerate() {
+
+    if i > 0 {
+      println!("===============================");
+    }
+
+    println!("{}\n", alert.properties.headline);
+    println!("{}\n", alert.properties.description);
+    println!("{}\n", alert.properties.instruction);
+    println!("{}\n", alert.properties.area_desc);
+  }
+
+}
+
+/// Look up alerts for `(lat, lon)` and print them to stdout, or an error
+/// message to stderr if the lookup failed.
+pub fn print_alert(lat: f64, lon: f64) {
+
+  match alerts_for(lat, lon) {
+    Ok(alerts) => print_alerts(&alerts),
+    Err(e) => eprintln!("Error: {}", e),
+  }
+
+}
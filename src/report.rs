@@ -0,0 +1,70 @@
+//! A cleaned, flattened projection of [`Alert`] for scripting-friendly output.
+//!
+//! The raw NOAA `Properties` struct is full of CAP/GeoJSON field-naming
+//! noise; `AlertSummary` keeps only the fields a script or dashboard is
+//! likely to want, with `onset`/`expires` parsed into proper timestamps.
+
+use crate::Alert;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Serialize)]
+pub struct AlertSummary {
+    pub event: String,
+    pub headline: String,
+    pub severity: String,
+    pub certainty: String,
+    pub urgency: String,
+    pub onset: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(rename = "areaDesc")]
+    pub area_desc: String,
+    pub instruction: String,
+}
+
+/// A report is just the flattened summaries for every matched alert.
+pub type Report = Vec<AlertSummary>;
+
+impl From<&Alert> for AlertSummary {
+    fn from(alert: &Alert) -> Self {
+        AlertSummary {
+            event: alert.properties.event.clone(),
+            headline: alert.properties.headline.clone(),
+            severity: alert.properties.severity.clone(),
+            certainty: alert.properties.certainty.clone(),
+            urgency: alert.properties.urgency.clone(),
+            onset: alert.properties.onset.parse().ok(),
+            expires: alert.properties.expires.parse().ok(),
+            area_desc: alert.properties.area_desc.clone(),
+            instruction: alert.properties.instruction.clone(),
+        }
+    }
+}
+
+impl From<Alert> for AlertSummary {
+    fn from(alert: Alert) -> Self {
+        AlertSummary::from(&alert)
+    }
+}
+
+/// Print `alerts` as a pretty-printed JSON `Report`.
+pub fn print_report_for(alerts: &[Alert]) {
+
+  let report: Report = alerts.iter().map(AlertSummary::from).collect();
+
+  match serde_json::to_string_pretty(&report) {
+    Ok(json) => println!("{}", json),
+    Err(e) => eprintln!("Error: failed to encode report as JSON: {}", e),
+  }
+
+}
+
+/// Fetch alerts for `(lat, lon)` and print them as a pretty-printed JSON
+/// `Report`, or an error message to stderr if the lookup failed.
+pub fn print_report(lat: f64, lon: f64) {
+
+  match crate::alerts_for(lat, lon) {
+    Ok(alerts) => print_report_for(&alerts),
+    Err(e) => eprintln!("Error: {}", e),
+  }
+
+}
@@ -0,0 +1,74 @@
+//! Long-lived monitor mode: poll `alerts_for_cached` on an interval
+//! and report only the alerts that appeared or cleared since the last poll.
+
+use crate::filter::{self, Severity};
+use crate::{alerts_for_cached, Alert, WxError};
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Poll alerts for `(lat, lon)` every `interval` on a background thread,
+/// printing `NEW: ...` when a CAP id appears that wasn't active on the
+/// previous poll and `CLEARED: ...` when one drops out. `min_severity` and
+/// `within_hours`, if set, are applied to each poll's results the same way
+/// as the one-shot lookup. `cache_ttl`/`force_refresh` are forwarded to the
+/// shapefile cache on every poll.
+#[allow(clippy::too_many_arguments)]
+pub fn watch(
+  lat: f64,
+  lon: f64,
+  interval: Duration,
+  min_severity: Option<Severity>,
+  within_hours: Option<i64>,
+  cache_ttl: Duration,
+  force_refresh: bool,
+) {
+
+  let (tx, rx) = mpsc::channel::<Result<Vec<Alert>, WxError>>();
+
+  thread::spawn(move || {
+    let client = reqwest::blocking::Client::new();
+
+    loop {
+      let result = alerts_for_cached(&client, lat, lon, cache_ttl, force_refresh);
+
+      if tx.send(result).is_err() {
+        break;
+      }
+
+      thread::sleep(interval);
+    }
+  });
+
+  let mut active: HashSet<String> = HashSet::new();
+
+  for result in rx {
+    match result {
+      Ok(mut alerts) => {
+        if let Some(min) = min_severity {
+          alerts = filter::filter_min_severity(alerts, min);
+        }
+        if let Some(hours) = within_hours {
+          alerts = filter::filter_within_hours(alerts, hours);
+        }
+
+        let current: HashSet<String> = alerts.iter().map(|a| a.id.clone()).collect();
+
+        for alert in &alerts {
+          if !active.contains(&alert.id) {
+            println!("NEW: {}", alert.properties.headline);
+          }
+        }
+
+        for id in active.difference(&current) {
+          println!("CLEARED: {}", id);
+        }
+
+        active = current;
+      }
+      Err(e) => eprintln!("Error: {}", e),
+    }
+  }
+
+}